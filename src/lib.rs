@@ -1,15 +1,30 @@
 extern crate lazy_static;
 
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::io::{self, Write};
 use std::fs::{self, File, OpenOptions};
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use time::macros::format_description;
 
-#[derive(Debug, Clone, Copy)]
+/// Size (in bytes) an async buffer is allowed to grow to before it is
+/// swapped out and handed to the background writer thread.
+const ASYNC_FLUSH_THRESHOLD: usize = 64 * 1024;
+/// Upper bound on how long buffered lines can sit before being written,
+/// even if the threshold above is never reached.
+const ASYNC_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Severity, ordered from most (`ERROR`) to least (`DEBUG`) severe so that
+/// `level <= max_level` reads naturally as "at least as severe as the
+/// configured threshold".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
     ERROR,
     WARN,
@@ -28,21 +43,182 @@ impl Level {
     }
 }
 
+/// Controls when `RollingFileWriter` rolls to a new file based on
+/// wall-clock period, independently of the `max_size` cap which always
+/// applies. `SizeOnly` keeps today's behaviour of rotating purely on size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    SizeOnly,
+    Minutely,
+    Hourly,
+    Daily,
+}
+
+impl Rotation {
+    /// Renders the period `now` falls into, e.g. `2024-06-01` for `Daily`
+    /// or `2024-06-01-14` for `Hourly`. Used both to name rolled files and
+    /// to detect when the open file has crossed into a new period.
+    fn period_suffix(&self, now: time::OffsetDateTime) -> Option<String> {
+        match self {
+            Rotation::SizeOnly => None,
+            Rotation::Daily => {
+                let format = format_description!("[year]-[month]-[day]");
+                Some(now.format(&format).unwrap_or_default())
+            }
+            Rotation::Hourly => {
+                let format = format_description!("[year]-[month]-[day]-[hour]");
+                Some(now.format(&format).unwrap_or_default())
+            }
+            Rotation::Minutely => {
+                let format = format_description!("[year]-[month]-[day]-[hour]-[minute]");
+                Some(now.format(&format).unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Selects how `Logger` renders each line. `Plain` is the original
+/// `ts [tid][LEVEL] msg` layout; `Json` emits one JSON object per line so
+/// aggregation pipelines can ingest records without regex parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Plain,
+    Json,
+}
+
+/// A structured field value attached via the `*_kv`-style macro calls
+/// (e.g. `info!(logger, "request done", status = 200, path = "/x")`).
+/// Under `Format::Json` each field becomes an extra JSON key; under
+/// `Format::Plain` it's appended as a trailing `key=value` pair.
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Str(s) => write!(f, "{}", s),
+            FieldValue::Int(i) => write!(f, "{}", i),
+            FieldValue::Float(v) => write!(f, "{}", v),
+            FieldValue::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+impl FieldValue {
+    /// Appends this value's JSON representation (no surrounding key) to `out`.
+    fn write_json(&self, out: &mut String) {
+        match self {
+            FieldValue::Str(s) => {
+                out.push('"');
+                escape_json_into(s, out);
+                out.push('"');
+            }
+            FieldValue::Int(i) => out.push_str(&i.to_string()),
+            FieldValue::Float(v) => out.push_str(&v.to_string()),
+            FieldValue::Bool(b) => out.push_str(&b.to_string()),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(v: &str) -> Self { FieldValue::Str(v.to_string()) }
+}
+impl From<String> for FieldValue {
+    fn from(v: String) -> Self { FieldValue::Str(v) }
+}
+impl From<bool> for FieldValue {
+    fn from(v: bool) -> Self { FieldValue::Bool(v) }
+}
+impl From<f32> for FieldValue {
+    fn from(v: f32) -> Self { FieldValue::Float(v as f64) }
+}
+impl From<f64> for FieldValue {
+    fn from(v: f64) -> Self { FieldValue::Float(v) }
+}
+
+macro_rules! impl_field_value_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for FieldValue {
+            fn from(v: $t) -> Self { FieldValue::Int(v as i64) }
+        })*
+    };
+}
+impl_field_value_from_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+fn current_time() -> time::OffsetDateTime {
+    time::OffsetDateTime::now_local().unwrap_or(time::OffsetDateTime::now_utc())
+}
+
 struct InnerState {
     file: File,
     current_size: u64,
+    /// The period suffix (see `Rotation::period_suffix`) the currently
+    /// open file was opened under, or `None` when rotation is `SizeOnly`.
+    period: Option<String>,
+    /// Kept alongside the file handle (rather than as a plain field on
+    /// `RollingFileWriter`) so `change_log_file` can swap it atomically
+    /// under the same lock that guards the handle it describes.
+    base_path: PathBuf,
+    /// Bytes written to `file` since the last `sync_all`. Reset whenever a
+    /// sync happens, rotation opens a new file, or the file is reopened.
+    bytes_since_sync: u64,
+}
+
+/// The pair of buffers an async `RollingFileWriter` appends into. `active`
+/// is the buffer application threads are currently writing to; the other
+/// slot is either empty or mid-flight to the background writer thread.
+struct AsyncBuffers {
+    bufs: [Vec<u8>; 2],
+    active: usize,
+}
+
+/// Background-writer bookkeeping for async mode. Kept separate from
+/// `InnerState` because it's guarded by its own lock (and a condvar),
+/// independently of the file handle.
+struct AsyncState {
+    buffers: Mutex<AsyncBuffers>,
+    notify: Condvar,
+    shutdown: AtomicBool,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Hands freshly-rotated segments off to a dedicated worker thread so
+/// gzip compression never blocks whichever thread triggered rotation.
+struct CompressionState {
+    sender: mpsc::Sender<PathBuf>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    /// Path the worker is currently compressing (or has just been handed),
+    /// if any. `rotate_locked` checks this before renaming a slot further
+    /// down the chain and waits on `done` if it would move the path out
+    /// from under the worker mid-compression.
+    pending: Arc<PendingCompression>,
+}
+
+struct PendingCompression {
+    path: Mutex<Option<PathBuf>>,
+    done: Condvar,
 }
 
 pub struct RollingFileWriter {
     state: Mutex<InnerState>,
-    base_path: PathBuf,
     max_size: u64,
     max_files: u32,
-    instant_flush: bool, 
+    instant_flush: bool,
+    bytes_per_sync: u64,
+    rotation: Rotation,
+    async_state: Option<AsyncState>,
+    compression: Option<CompressionState>,
 }
 
 impl RollingFileWriter {
-    fn new(base_path: PathBuf, max_size: u64, max_files: u32, instant_flush: bool) -> io::Result<Self> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(base_path: PathBuf, max_size: u64, max_files: u32, instant_flush: bool, bytes_per_sync: u64, is_async: bool, rotation: Rotation, compression: bool) -> io::Result<Self> {
         if let Some(parent) = base_path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -53,74 +229,451 @@ impl RollingFileWriter {
             .append(true)
             .open(&path)?;
         let size = file.metadata()?.len();
+        let period = rotation.period_suffix(current_time());
+
+        let async_state = if is_async {
+            Some(AsyncState {
+                buffers: Mutex::new(AsyncBuffers {
+                    bufs: [
+                        Vec::with_capacity(ASYNC_FLUSH_THRESHOLD),
+                        Vec::with_capacity(ASYNC_FLUSH_THRESHOLD),
+                    ],
+                    active: 0,
+                }),
+                notify: Condvar::new(),
+                shutdown: AtomicBool::new(false),
+                thread: Mutex::new(None),
+            })
+        } else {
+            None
+        };
+
+        let compression = if compression {
+            Some(Self::spawn_compression_worker())
+        } else {
+            None
+        };
 
         Ok(Self {
-            state: Mutex::new(InnerState { file, current_size: size }),
-            base_path,
+            state: Mutex::new(InnerState { file, current_size: size, period, base_path, bytes_since_sync: 0 }),
             max_size,
             max_files,
             instant_flush,
+            bytes_per_sync,
+            rotation,
+            async_state,
+            compression,
         })
     }
 
+    /// Spawns the dedicated gzip worker. Its queue is an `mpsc::Sender`
+    /// rather than anything keyed off the writer's own `Arc`, so the
+    /// worker never keeps `RollingFileWriter` itself alive.
+    fn spawn_compression_worker() -> CompressionState {
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let pending = Arc::new(PendingCompression { path: Mutex::new(None), done: Condvar::new() });
+        let worker_pending = Arc::clone(&pending);
+        let thread = std::thread::spawn(move || {
+            for path in receiver {
+                if let Err(e) = compress_and_remove(&path) {
+                    eprintln!("Log compression failed for {}: {}", path.display(), e);
+                }
+                let mut guard = worker_pending.path.lock();
+                if guard.as_deref() == Some(path.as_path()) {
+                    *guard = None;
+                }
+                worker_pending.done.notify_all();
+            }
+        });
+        CompressionState { sender, thread: Mutex::new(Some(thread)), pending }
+    }
+
+    fn is_async(&self) -> bool {
+        self.async_state.is_some()
+    }
+
+    /// Appends `buf` into the active async buffer, swapping and waking the
+    /// writer thread if it has grown past `ASYNC_FLUSH_THRESHOLD`.
+    fn append_async(&self, buf: &[u8]) {
+        let Some(async_state) = &self.async_state else { return };
+        let mut guard = async_state.buffers.lock();
+        let active = guard.active;
+        guard.bufs[active].extend_from_slice(buf);
+        let should_swap = guard.bufs[active].len() >= ASYNC_FLUSH_THRESHOLD;
+        drop(guard);
+
+        if should_swap {
+            async_state.notify.notify_one();
+        }
+    }
+
+    /// Swaps the active buffer out for the standby one and returns the
+    /// drained contents, or `None` if there's nothing to write.
+    fn swap_async_buffer(&self) -> Option<Vec<u8>> {
+        let async_state = self.async_state.as_ref()?;
+        let mut guard = async_state.buffers.lock();
+        let filled = guard.active;
+        if guard.bufs[filled].is_empty() {
+            return None;
+        }
+        guard.active = 1 - filled;
+        Some(std::mem::replace(&mut guard.bufs[filled], Vec::with_capacity(ASYNC_FLUSH_THRESHOLD)))
+    }
+
+    /// Drains whatever is currently buffered and writes it out synchronously.
+    /// Used for explicit `Logger::flush()` calls; does not touch the
+    /// background thread.
+    fn flush_async_buffer(&self) -> io::Result<()> {
+        if let Some(buf) = self.swap_async_buffer() {
+            let mut state = self.state.lock();
+            self.write_locked(&mut state, &buf)?;
+            state.file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Spawns the background writer thread. Must be called once the writer
+    /// is already behind an `Arc`, since the thread only ever holds a
+    /// `Weak` reference so it never keeps the writer alive on its own.
+    fn spawn_async_writer(self: &Arc<Self>) {
+        let Some(async_state) = &self.async_state else { return };
+        let weak = Arc::downgrade(self);
+        let handle = std::thread::spawn(move || loop {
+            let Some(writer) = weak.upgrade() else { break };
+            let async_state = writer.async_state.as_ref().unwrap();
+
+            if let Some(buf) = writer.swap_async_buffer() {
+                let mut state = writer.state.lock();
+                // `write_locked` already applies `instant_flush` and
+                // `bytes_per_sync` itself; syncing again here unconditionally
+                // would make `bytes_per_sync` a no-op under async mode.
+                if let Err(e) = writer.write_locked(&mut state, &buf) {
+                    eprintln!("Async log writer failed: {}", e);
+                }
+            }
+
+            if async_state.shutdown.load(Ordering::Acquire) {
+                break;
+            }
+
+            let mut guard = async_state.buffers.lock();
+            let active = guard.active;
+            if guard.bufs[active].len() < ASYNC_FLUSH_THRESHOLD
+                && !async_state.shutdown.load(Ordering::Acquire)
+            {
+                async_state.notify.wait_for(&mut guard, ASYNC_FLUSH_INTERVAL);
+            }
+        });
+        async_state.thread.lock().replace(handle);
+    }
+
+    /// Signals the writer thread to drain both buffers one last time and
+    /// joins it. Called when the last `Logger` handle sharing this writer
+    /// is dropped.
+    fn shutdown_async(&self) {
+        let Some(async_state) = &self.async_state else { return };
+        async_state.shutdown.store(true, Ordering::Release);
+        async_state.notify.notify_one();
+        if let Some(handle) = async_state.thread.lock().take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn write_locked(&self, state: &mut InnerState, buf: &[u8]) -> io::Result<()> {
+        if let Some(new_period) = self.rotation.period_suffix(current_time()) {
+            if state.period.as_deref() != Some(new_period.as_str()) {
+                if let Err(e) = self.rotate_time_locked(state, new_period) {
+                    eprintln!("Time-based log rotation failed: {}", e);
+                }
+            }
+        }
+
+        if state.current_size + buf.len() as u64 > self.max_size {
+            if let Err(e) = self.rotate_locked(state) {
+                eprintln!("Log rotation failed: {}", e);
+            }
+        }
+
+        let written = state.file.write(buf)?;
+        state.current_size += written as u64;
+
+        if self.instant_flush {
+            state.file.flush()?;
+        }
+
+        if self.bytes_per_sync > 0 {
+            state.bytes_since_sync += written as u64;
+            if state.bytes_since_sync >= self.bytes_per_sync {
+                state.file.sync_all()?;
+                state.bytes_since_sync = 0;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rolls the live file out to a period-suffixed name (e.g.
+    /// `app.2024-06-01.log`) when the wall-clock period has moved on from
+    /// the one the file was opened under, then starts a fresh file for
+    /// `new_period`.
+    fn rotate_time_locked(&self, state: &mut InnerState, new_period: String) -> io::Result<()> {
+        if let Some(old_period) = state.period.take() {
+            state.file.sync_all()?;
+
+            let dst = state.base_path.with_extension(format!("{}.log", old_period));
+            if dst.exists() {
+                let _ = fs::remove_file(&dst);
+            }
+            let current_path = state.base_path.with_extension("log");
+            let _ = fs::rename(&current_path, &dst);
+
+            self.cleanup_period_files(&state.base_path);
+        }
+
+        let path = state.base_path.with_extension("log");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+
+        state.file = file;
+        state.current_size = 0;
+        state.bytes_since_sync = 0;
+        state.period = Some(new_period);
+
+        Ok(())
+    }
+
+    /// Deletes the oldest period-suffixed files beyond the `max_files`
+    /// retention cap.
+    fn cleanup_period_files(&self, base_path: &Path) {
+        let Some(parent) = base_path.parent() else { return };
+        let Some(stem) = base_path.file_name().and_then(|s| s.to_str()) else { return };
+        let prefix = format!("{}.", stem);
+        let live_name = format!("{}.log", stem);
+
+        let Ok(entries) = fs::read_dir(parent) else { return };
+        let mut period_files: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| {
+                        n != live_name
+                            && n.starts_with(&prefix)
+                            && n.ends_with(".log")
+                            && self.is_period_suffixed_name(n, &prefix)
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        period_files.sort();
+        while period_files.len() > self.max_files as usize {
+            let oldest = period_files.remove(0);
+            let _ = fs::remove_file(oldest);
+        }
+    }
+
+    /// True if `name` is `{prefix}{period}.log` with `period` shaped like
+    /// `Rotation::period_suffix`'s output for `self.rotation` (e.g.
+    /// `2024-06-01`), as opposed to a numeric size-rotation segment or the
+    /// live file sharing the same `{prefix}...log` shape.
+    fn is_period_suffixed_name(&self, name: &str, prefix: &str) -> bool {
+        let Some(middle) = name.strip_prefix(prefix).and_then(|s| s.strip_suffix(".log")) else {
+            return false;
+        };
+        let expected_lens: &[usize] = match self.rotation {
+            Rotation::SizeOnly => return false,
+            Rotation::Daily => &[4, 2, 2],
+            Rotation::Hourly => &[4, 2, 2, 2],
+            Rotation::Minutely => &[4, 2, 2, 2, 2],
+        };
+
+        let segments: Vec<&str> = middle.split('-').collect();
+        segments.len() == expected_lens.len()
+            && segments
+                .iter()
+                .zip(expected_lens)
+                .all(|(seg, &len)| seg.len() == len && seg.bytes().all(|b| b.is_ascii_digit()))
+    }
+
     fn rotate_locked(&self, state: &mut InnerState) -> io::Result<()> {
         if state.current_size < self.max_size {
             return Ok(());
         }
 
         state.file.sync_all()?;
-        
-        let get_path = |idx: u32| -> PathBuf {
+
+        let base_path = state.base_path.clone();
+        let plain_path = |idx: u32| -> PathBuf {
             if idx == 0 {
-                self.base_path.with_extension("log")
+                base_path.with_extension("log")
+            } else {
+                base_path.with_extension(format!("{}.log", idx))
+            }
+        };
+        let gz_path = |idx: u32| -> PathBuf {
+            let mut name = plain_path(idx).into_os_string();
+            name.push(".gz");
+            PathBuf::from(name)
+        };
+        // A segment sits as plain for a moment while compression is still
+        // in flight on the background worker, so check both forms.
+        let existing_path = |idx: u32| -> Option<PathBuf> {
+            let gz = gz_path(idx);
+            if gz.exists() {
+                Some(gz)
             } else {
-                self.base_path.with_extension(format!("{}.log", idx))
+                let plain = plain_path(idx);
+                plain.exists().then_some(plain)
             }
         };
 
+        // The only slot a compression job is ever queued against is slot 1
+        // (`dst` below is always `plain_path(1)` when `i == 0`), so a single
+        // wait up front — before *any* path in this call is resolved or
+        // touched — is enough to cover every way this loop can reach that
+        // slot: shifting it further out (`src` at `i == 1`) or clearing it
+        // to make room for the live file (`existing_path(i + 1)` at
+        // `i == 0`). Racing either against the worker's still-in-flight
+        // `File::open`/`fs::remove_file` on that same path is what used to
+        // produce "No such file or directory" and leave the segment plain.
+        if let Some(compression) = &self.compression {
+            let candidate = plain_path(1);
+            let mut guard = compression.pending.path.lock();
+            while guard.as_deref() == Some(candidate.as_path()) {
+                compression.pending.done.wait(&mut guard);
+            }
+        }
+
         for i in (0..self.max_files - 1).rev() {
-            let src = get_path(i);
-            let dst = get_path(i + 1);
-            
-            if src.exists() {
-                if dst.exists() {
-                    let _ = fs::remove_file(&dst); 
+            let Some(src) = existing_path(i) else { continue };
+
+            if let Some(dst) = existing_path(i + 1) {
+                let _ = fs::remove_file(&dst);
+            }
+
+            let dst = if i == 0 {
+                // The live file is always plain; the background worker
+                // compresses it once it has landed in its slot.
+                plain_path(i + 1)
+            } else if src.extension().and_then(|e| e.to_str()) == Some("gz") {
+                gz_path(i + 1)
+            } else {
+                plain_path(i + 1)
+            };
+
+            let _ = fs::rename(&src, &dst);
+
+            if i == 0 {
+                if let Some(compression) = &self.compression {
+                    *compression.pending.path.lock() = Some(dst.clone());
+                    let _ = compression.sender.send(dst);
                 }
-                let _ = fs::rename(&src, &dst);
             }
         }
 
-        let path = self.base_path.with_extension("log");
+        let path = state.base_path.with_extension("log");
         let file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)?;
-        
+
         state.file = file;
         state.current_size = 0;
-        
+        state.bytes_since_sync = 0;
+
         Ok(())
     }
-}
 
-impl Write for &RollingFileWriter {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    /// Closes and reopens the file at the writer's current `base_path`,
+    /// without tearing down the logger or losing any buffered data. Used
+    /// to cooperate with an external tool that `rename`s the live log file
+    /// out from under this process — call this after the rename so
+    /// subsequent writes land in a fresh file at the same path.
+    fn reopen(&self) -> io::Result<()> {
         let mut state = self.state.lock();
-        
-        if state.current_size + buf.len() as u64 > self.max_size {
-            if let Err(e) = self.rotate_locked(&mut state) {
-                eprintln!("Log rotation failed: {}", e);
-            }
+        let _ = state.file.flush();
+
+        let path = state.base_path.with_extension("log");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size = file.metadata()?.len();
+
+        state.file = file;
+        state.current_size = size;
+        state.bytes_since_sync = 0;
+        state.period = self.rotation.period_suffix(current_time());
+
+        Ok(())
+    }
+
+    /// Atomically swaps the destination path under the lock and opens the
+    /// new file, e.g. to redirect output from a temp file to a
+    /// user-chosen path once config has loaded.
+    fn change_log_file(&self, new_path: PathBuf) -> io::Result<()> {
+        if let Some(parent) = new_path.parent() {
+            fs::create_dir_all(parent)?;
         }
 
-        let written = state.file.write(buf)?;
-        state.current_size += written as u64;
-        
-        if self.instant_flush {
-            state.file.flush()?;
+        let mut state = self.state.lock();
+        let _ = state.file.flush();
+
+        let path = new_path.with_extension("log");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size = file.metadata()?.len();
+
+        state.base_path = new_path;
+        state.file = file;
+        state.current_size = size;
+        state.bytes_since_sync = 0;
+        state.period = self.rotation.period_suffix(current_time());
+
+        Ok(())
+    }
+}
+
+impl Drop for RollingFileWriter {
+    fn drop(&mut self) {
+        if let Some(compression) = self.compression.take() {
+            // Dropping the sender disconnects the channel, letting the
+            // worker's `for path in receiver` loop end once it drains.
+            drop(compression.sender);
+            if let Some(handle) = compression.thread.lock().take() {
+                let _ = handle.join();
+            }
         }
-        
-        Ok(written)
+    }
+}
+
+/// Gzips `path` to `path` + `.gz` and removes the uncompressed original.
+fn compress_and_remove(path: &Path) -> io::Result<()> {
+    let mut gz_name = path.as_os_str().to_os_string();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+
+    let mut input = File::open(path)?;
+    let output = File::create(&gz_path)?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+impl Write for &RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock();
+        self.write_locked(&mut state, buf)?;
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -134,11 +687,41 @@ pub struct WriterWrapper(pub(crate) Arc<RollingFileWriter>);
 
 impl Write for WriterWrapper {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        (&*self.0).write(buf)
+        if self.0.is_async() {
+            self.0.append_async(buf);
+            Ok(buf.len())
+        } else {
+            (&*self.0).write(buf)
+        }
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        (&*self.0).flush()
+        if self.0.is_async() {
+            self.0.flush_async_buffer()
+        } else {
+            (&*self.0).flush()
+        }
+    }
+}
+
+/// Where a `Sink` ultimately writes. `File` goes through the usual
+/// `RollingFileWriter` machinery (rotation, size tracking, ...); the other
+/// variants bypass that entirely since there's no file to rotate.
+pub enum LogDestination {
+    File(PathBuf),
+    Stdout,
+    Stderr,
+    Custom(Box<dyn Write + Send>),
+}
+
+impl std::fmt::Debug for LogDestination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogDestination::File(path) => f.debug_tuple("File").field(path).finish(),
+            LogDestination::Stdout => write!(f, "Stdout"),
+            LogDestination::Stderr => write!(f, "Stderr"),
+            LogDestination::Custom(_) => write!(f, "Custom(..)"),
+        }
     }
 }
 
@@ -149,8 +732,25 @@ pub struct LogConfig {
     max_size: u64,
     is_async: bool,
     instant_flush: bool,
+    bytes_per_sync: u64,
     file_name: String,
     instance_name: String,
+    rotation: Rotation,
+    max_level: Level,
+    primary_destination: Option<LogDestination>,
+    extra_sinks: Vec<SinkSpec>,
+    compression: bool,
+    format: Format,
+}
+
+/// A secondary destination requested via `LogConfig::add_sink`. Built into
+/// a real `Sink` by `init_logger`; `File` destinations share every other
+/// setting (path, rotation, size cap, ...) with the primary sink, but every
+/// destination filters independently on `level`.
+#[derive(Debug)]
+struct SinkSpec {
+    destination: LogDestination,
+    level: Level,
 }
 
 impl Default for LogConfig {
@@ -161,8 +761,15 @@ impl Default for LogConfig {
             max_size: 20 * 1024 * 1024,
             is_async: false,
             instant_flush: false,
+            bytes_per_sync: 0,
             file_name: String::from("app"),
             instance_name: String::from("default"),
+            rotation: Rotation::SizeOnly,
+            max_level: Level::DEBUG,
+            primary_destination: None,
+            extra_sinks: Vec::new(),
+            compression: false,
+            format: Format::Plain,
         }
     }
 }
@@ -174,19 +781,132 @@ impl LogConfig {
     pub fn with_max_size(mut self, size: u64) -> Self { self.max_size = size; self }
     pub fn with_async(mut self, is_async: bool) -> Self { self.is_async = is_async; self }
     pub fn with_instant_flush(mut self, instant_flush: bool) -> Self { self.instant_flush = instant_flush; self }
+    /// Calls `sync_all` once at least `n` bytes have been written since the
+    /// last sync, bounding durability exposure without paying a syscall per
+    /// line the way `with_instant_flush(true)` does. `0` disables this.
+    pub fn with_bytes_per_sync(mut self, n: u64) -> Self { self.bytes_per_sync = n; self }
     pub fn with_file_name<S: Into<String>>(mut self, name: S) -> Self { self.file_name = name.into(); self }
     pub fn with_instance_name(mut self, name: &str) -> Self { self.instance_name = name.to_string(); self }
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self { self.rotation = rotation; self }
+    /// Messages less severe than `level` are dropped before formatting.
+    pub fn with_max_level(mut self, level: Level) -> Self { self.max_level = level; self }
+    /// Overrides the primary destination (a rolling file under `log_path`
+    /// by default) with any `LogDestination` — e.g. `Stdout` during
+    /// development, switched back to a file in production with no other
+    /// code changes.
+    pub fn with_destination(mut self, destination: LogDestination) -> Self {
+        self.primary_destination = Some(destination);
+        self
+    }
+    /// Adds another destination that only receives messages at least as
+    /// severe as `level` — e.g. a `warnings.log` fed alongside the main
+    /// debug-and-above file, or a `Stdout` mirror of everything.
+    pub fn add_sink(mut self, destination: LogDestination, level: Level) -> Self {
+        self.extra_sinks.push(SinkSpec { destination, level });
+        self
+    }
+    /// Gzips each segment `rotate_locked` rolls out to `app.1.log.gz`
+    /// instead of leaving it as plain `app.1.log`.
+    pub fn with_compression(mut self, compression: bool) -> Self { self.compression = compression; self }
+    /// Switches the rendered line layout; see `Format`.
+    pub fn with_format(mut self, format: Format) -> Self { self.format = format; self }
+}
+
+/// A writer resolved from a `LogDestination`. `Stdout`/`Stderr` skip
+/// rotation and size tracking entirely; `Custom` wraps the user's writer in
+/// the same kind of `Mutex`-guarded flush logic the file path uses.
+#[derive(Clone)]
+enum ResolvedWriter {
+    Rolling(WriterWrapper),
+    Stdout,
+    Stderr,
+    Custom(Arc<Mutex<Box<dyn Write + Send>>>),
+}
+
+impl ResolvedWriter {
+    fn write_line(&self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            ResolvedWriter::Rolling(writer) => writer.clone().write_all(buf),
+            ResolvedWriter::Stdout => io::stdout().write_all(buf),
+            ResolvedWriter::Stderr => io::stderr().write_all(buf),
+            ResolvedWriter::Custom(writer) => writer.lock().write_all(buf),
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        match self {
+            ResolvedWriter::Rolling(writer) => writer.clone().flush(),
+            ResolvedWriter::Stdout => io::stdout().flush(),
+            ResolvedWriter::Stderr => io::stderr().flush(),
+            ResolvedWriter::Custom(writer) => writer.lock().flush(),
+        }
+    }
+
+    /// Tears down the background writer thread if this is the last handle
+    /// sharing a `Rolling` destination's writer; a no-op for every other
+    /// destination, since only `Rolling` ever has one.
+    fn shutdown_if_last(&self) {
+        if let ResolvedWriter::Rolling(writer) = self {
+            if Arc::strong_count(&writer.0) == 1 {
+                writer.0.shutdown_async();
+            }
+        }
+    }
+
+    /// Reopens the file handle, if this is a `Rolling` destination; a
+    /// no-op otherwise.
+    fn reopen(&self) -> io::Result<()> {
+        match self {
+            ResolvedWriter::Rolling(writer) => writer.0.reopen(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Redirects to `new_path`, if this is a `Rolling` destination; a
+    /// no-op otherwise.
+    fn change_log_file(&self, new_path: &Path) -> io::Result<()> {
+        match self {
+            ResolvedWriter::Rolling(writer) => writer.0.change_log_file(new_path.to_path_buf()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One logging destination: a writer plus the threshold a message's level
+/// must meet (be at least as severe as) to be sent there.
+#[derive(Clone)]
+struct Sink {
+    writer: ResolvedWriter,
+    level: Level,
 }
 
 #[derive(Clone)]
 pub struct Logger {
-    writer: WriterWrapper,
+    sinks: Vec<Sink>,
+    max_level: Level,
+    format: Format,
     #[allow(dead_code)]
     instance_name: String,
 }
 
 impl Logger {
     pub fn log(&self, level: Level, message: &str) -> io::Result<()> {
+        self.log_with_fields(level, message, &[])
+    }
+
+    /// Like `log`, but attaches structured key/value fields (used by the
+    /// `info!`/`warn!`/`error!`/`debug!`/`fatal!` macros' `key = value`
+    /// form). Under `Format::Json` each field becomes an extra JSON key;
+    /// under `Format::Plain` it's appended as a trailing `key=value` pair.
+    pub fn log_kv(&self, level: Level, message: &str, fields: &[(&str, FieldValue)]) -> io::Result<()> {
+        self.log_with_fields(level, message, fields)
+    }
+
+    fn log_with_fields(&self, level: Level, message: &str, fields: &[(&str, FieldValue)]) -> io::Result<()> {
+        if level > self.max_level {
+            return Ok(());
+        }
+
         thread_local! {
             static THREAD_ID_STR: String = {
                 let thread_id = std::thread::current().id();
@@ -197,57 +917,197 @@ impl Logger {
             };
         }
 
-        let log_line = THREAD_ID_STR.with(|tid_str| {
-            format_log_message(level.as_str(), tid_str, message)
+        let log_line = THREAD_ID_STR.with(|tid_str| match self.format {
+            Format::Plain => format_log_message_plain(level.as_str(), tid_str, message, fields),
+            Format::Json => format_log_message_json(level.as_str(), tid_str, message, fields),
         });
-        
-        let mut writer = self.writer.clone();
-        writer.write_all(log_line.as_bytes())
+
+        for sink in &self.sinks {
+            if level <= sink.level {
+                sink.writer.write_line(log_line.as_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forces any buffered lines out to disk right now, across every sink.
+    /// In sync mode this is just a file flush; in async mode it drains the
+    /// active buffer without stopping the background writer thread, so
+    /// logging can carry on normally afterwards.
+    pub fn flush(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Closes and reopens every file-backed sink's handle, without
+    /// tearing down the logger or losing any buffered data. Cooperates
+    /// with external rotation tools that `rename` a live log out from
+    /// under this process: call this after the external move so
+    /// subsequent writes land in a fresh file at the same path.
+    pub fn reopen(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.writer.reopen()?;
+        }
+        Ok(())
+    }
+
+    /// Atomically redirects the primary sink's destination to `new_path`
+    /// at runtime, e.g. switching from a temp file to a user-chosen path
+    /// once config has loaded. Secondary sinks added via `add_sink` are
+    /// untouched.
+    pub fn change_log_file<P: AsRef<Path>>(&self, new_path: P) -> io::Result<()> {
+        if let Some(primary) = self.sinks.first() {
+            primary.writer.change_log_file(new_path.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Logger {
+    fn drop(&mut self) {
+        // Only the last handle sharing a given sink's writer should tear
+        // down its background thread; earlier clones just go away normally.
+        for sink in &self.sinks {
+            sink.writer.shutdown_if_last();
+        }
     }
 }
 
-fn format_log_message(level: &str, thread_id_str: &str, message: &str) -> String {
-    let now = time::OffsetDateTime::now_local().unwrap_or(time::OffsetDateTime::now_utc());
+fn format_log_message_plain(
+    level: &str,
+    thread_id_str: &str,
+    message: &str,
+    fields: &[(&str, FieldValue)],
+) -> String {
+    let now = current_time();
     let format = format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]");
     let timestamp = now.format(&format).unwrap_or_default();
-    
-    format!(
-        "{} [{}][{:<5}] {}\n",
+
+    let mut line = format!(
+        "{} [{}][{:<5}] {}",
         timestamp,
         thread_id_str,
         level,
         message
-    )
+    );
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    line.push('\n');
+    line
 }
 
-pub fn init_logger(config: LogConfig) -> Result<Logger, io::Error> {
-    let instance_name = config.instance_name.clone();
-    
-    let log_dir = &config.log_path;
-    std::fs::create_dir_all(log_dir)?;
+/// Renders one JSON object per line: `{"ts":"...","level":"...",
+/// "thread":"...","msg":"..."}`, plus any structured fields as extra keys.
+fn format_log_message_json(
+    level: &str,
+    thread_id_str: &str,
+    message: &str,
+    fields: &[(&str, FieldValue)],
+) -> String {
+    let now = current_time();
+    let format = format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]");
+    let timestamp = now.format(&format).unwrap_or_default();
 
-    let file_stem = Path::new(&config.file_name).file_stem().unwrap_or(std::ffi::OsStr::new("app"));
-    let log_path = log_dir.join(file_stem); 
+    let mut out = String::with_capacity(64 + message.len());
+    out.push_str("{\"ts\":\"");
+    escape_json_into(&timestamp, &mut out);
+    out.push_str("\",\"level\":\"");
+    escape_json_into(level, &mut out);
+    out.push_str("\",\"thread\":\"");
+    escape_json_into(thread_id_str, &mut out);
+    out.push_str("\",\"msg\":\"");
+    escape_json_into(message, &mut out);
+    out.push('"');
+    for (key, value) in fields {
+        out.push_str(",\"");
+        escape_json_into(key, &mut out);
+        out.push_str("\":");
+        value.write_json(&mut out);
+    }
+    out.push_str("}\n");
+    out
+}
 
-    if config.is_async {
-        eprintln!("Warning: Async logging requested but not implemented. Falling back to sync.");
+/// Escapes `s` for embedding inside a JSON string literal and appends it to `out`.
+fn escape_json_into(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
     }
+}
+
+/// Resolves a `LogDestination` into a `Sink`. `File` goes through the usual
+/// `RollingFileWriter` under `log_dir`, sharing every other setting in
+/// `config`; the other destinations skip that machinery entirely.
+fn build_sink(log_dir: &Path, destination: LogDestination, config: &LogConfig, level: Level) -> io::Result<Sink> {
+    let writer = match destination {
+        LogDestination::File(path) => {
+            let file_stem = path.file_stem().unwrap_or(std::ffi::OsStr::new("app"));
+            let log_path = log_dir.join(file_stem);
+
+            let rolling_writer = Arc::new(RollingFileWriter::new(
+                log_path,
+                config.max_size,
+                config.max_files,
+                config.instant_flush,
+                config.bytes_per_sync,
+                config.is_async,
+                config.rotation,
+                config.compression,
+            )?);
+            rolling_writer.spawn_async_writer();
+
+            ResolvedWriter::Rolling(WriterWrapper(rolling_writer))
+        }
+        LogDestination::Stdout => ResolvedWriter::Stdout,
+        LogDestination::Stderr => ResolvedWriter::Stderr,
+        LogDestination::Custom(writer) => ResolvedWriter::Custom(Arc::new(Mutex::new(writer))),
+    };
+
+    Ok(Sink { writer, level })
+}
+
+pub fn init_logger(mut config: LogConfig) -> Result<Logger, io::Error> {
+    let instance_name = config.instance_name.clone();
+
+    let log_dir = config.log_path.clone();
 
-    let file_writer = WriterWrapper(Arc::new(RollingFileWriter::new(
-        log_path,
-        config.max_size,
-        config.max_files,
-        config.instant_flush,
-    )?));
+    let primary_destination = config
+        .primary_destination
+        .take()
+        .unwrap_or_else(|| LogDestination::File(PathBuf::from(&config.file_name)));
+    let extra_sinks = std::mem::take(&mut config.extra_sinks);
+
+    let mut sinks = Vec::with_capacity(1 + extra_sinks.len());
+    sinks.push(build_sink(&log_dir, primary_destination, &config, Level::DEBUG)?);
+    for extra in extra_sinks {
+        sinks.push(build_sink(&log_dir, extra.destination, &config, extra.level)?);
+    }
 
     Ok(Logger {
-        writer: file_writer,
+        sinks,
+        max_level: config.max_level,
+        format: config.format,
         instance_name,
     })
 }
 
 #[macro_export]
 macro_rules! info {
+    ($logger:expr, $msg:expr $(, $key:ident = $val:expr)+ $(,)?) => {{
+        let _ = $logger.log_kv($crate::Level::INFO, $msg, &[$((stringify!($key), $crate::FieldValue::from($val))),+]);
+    }};
     ($logger:expr, $($arg:tt)*) => {{
         let _ = $logger.log($crate::Level::INFO, &format!($($arg)*));
     }};
@@ -255,6 +1115,9 @@ macro_rules! info {
 
 #[macro_export]
 macro_rules! error {
+    ($logger:expr, $msg:expr $(, $key:ident = $val:expr)+ $(,)?) => {{
+        let _ = $logger.log_kv($crate::Level::ERROR, $msg, &[$((stringify!($key), $crate::FieldValue::from($val))),+]);
+    }};
     ($logger:expr, $($arg:tt)*) => {{
         let _ = $logger.log($crate::Level::ERROR, &format!($($arg)*));
     }};
@@ -262,6 +1125,9 @@ macro_rules! error {
 
 #[macro_export]
 macro_rules! warn {
+    ($logger:expr, $msg:expr $(, $key:ident = $val:expr)+ $(,)?) => {{
+        let _ = $logger.log_kv($crate::Level::WARN, $msg, &[$((stringify!($key), $crate::FieldValue::from($val))),+]);
+    }};
     ($logger:expr, $($arg:tt)*) => {{
         let _ = $logger.log($crate::Level::WARN, &format!($($arg)*));
     }};
@@ -269,6 +1135,9 @@ macro_rules! warn {
 
 #[macro_export]
 macro_rules! debug {
+    ($logger:expr, $msg:expr $(, $key:ident = $val:expr)+ $(,)?) => {{
+        let _ = $logger.log_kv($crate::Level::DEBUG, $msg, &[$((stringify!($key), $crate::FieldValue::from($val))),+]);
+    }};
     ($logger:expr, $($arg:tt)*) => {{
         let _ = $logger.log($crate::Level::DEBUG, &format!($($arg)*));
     }};
@@ -276,8 +1145,147 @@ macro_rules! debug {
 
 #[macro_export]
 macro_rules! fatal {
+    ($logger:expr, $msg:expr $(, $key:ident = $val:expr)+ $(,)?) => {{
+        let _ = $logger.log_kv($crate::Level::ERROR, &format!("FATAL: {}", $msg), &[$((stringify!($key), $crate::FieldValue::from($val))),+]);
+        std::process::exit(1);
+    }};
     ($logger:expr, $($arg:tt)*) => {{
         let _ = $logger.log($crate::Level::ERROR, &format!("FATAL: {}", format!($($arg)*)));
         std::process::exit(1);
     }};
 }
+
+#[cfg(test)]
+mod async_writer_tests {
+    use super::*;
+    use std::thread;
+
+    /// Regression test for the double-buffered async writer: concurrent
+    /// appenders must never lose or corrupt a line across a buffer swap.
+    #[test]
+    fn concurrent_writers_all_land_in_file() {
+        let dir = std::env::temp_dir().join(format!("rs_loglib_test_async_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let config = LogConfig::new()
+            .with_path(dir.to_str().unwrap())
+            .with_file_name("app")
+            .with_async(true);
+        let logger = init_logger(config).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let logger = logger.clone();
+                thread::spawn(move || {
+                    for i in 0..200 {
+                        logger.log(Level::INFO, &format!("thread {} line {}", t, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        logger.flush().unwrap();
+        drop(logger);
+
+        let content = fs::read_to_string(dir.join("app.log")).unwrap();
+        assert_eq!(content.lines().count(), 8 * 200);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod retention_tests {
+    use super::*;
+
+    /// Regression test for the period-file retention glob: pruning must
+    /// only ever touch period-suffixed files, never numeric size-rotation
+    /// segments or the live file, even though they share the same
+    /// `{prefix}...log` shape.
+    #[test]
+    fn cleanup_period_files_ignores_numeric_and_live_files() {
+        let dir = std::env::temp_dir().join(format!("rs_loglib_test_retention_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let base_path = dir.join("app");
+        let writer = RollingFileWriter::new(base_path.clone(), 1024, 2, false, 0, false, Rotation::Daily, false).unwrap();
+
+        for name in [
+            "app.log",
+            "app.1.log",
+            "app.2.log",
+            "app.2024-01-01.log",
+            "app.2024-01-02.log",
+            "app.2024-01-03.log",
+        ] {
+            fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        writer.cleanup_period_files(&base_path);
+
+        let remaining: std::collections::HashSet<String> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+
+        assert!(remaining.contains("app.log"), "live file must survive pruning");
+        assert!(remaining.contains("app.1.log"), "numeric size-rotation segment must survive pruning");
+        assert!(remaining.contains("app.2.log"), "numeric size-rotation segment must survive pruning");
+        assert!(!remaining.contains("app.2024-01-01.log"), "oldest period file should have been pruned");
+        assert!(remaining.contains("app.2024-01-02.log"));
+        assert!(remaining.contains("app.2024-01-03.log"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod compression_rotation_tests {
+    use super::*;
+
+    /// Regression test for the rotation/compression race: every segment
+    /// `rotate_locked` shifts past slot 0 must eventually end up `.gz`,
+    /// even when rotations happen faster than the background worker can
+    /// keep up with.
+    #[test]
+    fn sustained_rotation_compresses_every_segment() {
+        let dir = std::env::temp_dir().join(format!("rs_loglib_test_rotate_gz_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let config = LogConfig::new()
+            .with_path(dir.to_str().unwrap())
+            .with_file_name("app")
+            .with_max_size(256)
+            .with_max_files(4)
+            .with_compression(true);
+        let logger = init_logger(config).unwrap();
+
+        for i in 0..2000 {
+            logger.log(Level::INFO, &format!("padding line number {}", i)).unwrap();
+        }
+        logger.flush().unwrap();
+        drop(logger);
+
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+
+        for name in &names {
+            assert!(
+                name == "app.log" || name.ends_with(".gz"),
+                "segment left uncompressed: {name}"
+            );
+        }
+        assert!(names.len() > 1, "rotation should have produced at least one segment");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}